@@ -0,0 +1,515 @@
+// The `entrypoint!` macro expands to `cfg(target_os = "solana")` and
+// `cfg(feature = "custom-heap"/"custom-panic")` gates that rustc's check-cfg
+// lint doesn't know about off-chain; silence them crate-wide.
+#![allow(unexpected_cfgs)]
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    program_pack::Pack,
+    sysvar::{rent::Rent, Sysvar},
+};
+
+/// Flash-loan fee charged on the borrowed amount, in basis points.
+const FLASH_LOAN_FEE_BPS: u128 = 100;
+
+/// A flash loan was not repaid in full (principal + fee) by its callback.
+const ERR_REPAYMENT_SHORTFALL: u32 = 1;
+/// An account the program writes is no longer rent-exempt.
+const ERR_NOT_RENT_EXEMPT: u32 = 2;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Global state for the reflection token.
+///
+/// Accounting follows the classic reflection model: balances live in
+/// "r-space" (the reflected space) while the user-visible supply lives in
+/// "t-space". A holder only ever stores `r_owned`; their displayed balance
+/// is derived on the fly through the current conversion `rate`. Charging a
+/// fee simply shrinks `r_total`, which raises the rate and lifts every
+/// remaining holder's derived balance proportionally — the reflection
+/// happens in O(1) without ever touching individual accounts.
+#[derive(Default, BorshSerialize, BorshDeserialize)]
+pub struct ReflectionToken {
+    /// Real token supply (t-space).
+    pub t_total: u128,
+    /// Reflected supply (r-space); shrinks as fees are reflected.
+    pub r_total: u128,
+    /// Running total of fees reflected to holders, in t-space, for reporting.
+    pub t_fee_total: u128,
+    /// Account permitted to perform privileged actions (mint, burn, admin).
+    pub authority: Pubkey,
+}
+
+/// Per-holder account: only the reflected balance is stored.
+#[derive(Default, BorshSerialize, BorshDeserialize)]
+pub struct Holder {
+    pub r_owned: u128,
+}
+
+/// Self-describing, Borsh-encoded wire format for the program's instructions.
+///
+/// Clients serialize one of these variants into `instruction_data`; the
+/// program decodes it with `try_from_slice`, so malformed buffers surface
+/// as `InvalidInstructionData` instead of panicking on a short slice.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub enum ReflectionInstruction {
+    /// Initialize the token account with the given real supply.
+    Initialize { total_supply: u64 },
+    /// Transfer `amount` t-space tokens from sender to recipient.
+    Transfer { amount: u64 },
+    /// Mint `amount` t-space tokens to a target account. Authority only.
+    Mint { amount: u64 },
+    /// Burn `amount` t-space tokens from a target account. Authority only.
+    Burn { amount: u64 },
+    /// Hand the authority to a new account. Authority only.
+    SetAuthority { new: Pubkey },
+    /// Lend `amount` tokens to a borrower for the duration of a single
+    /// transaction, requiring repayment plus a fee before returning.
+    FlashLoan { amount: u64 },
+}
+
+impl ReflectionToken {
+    pub fn new(total_supply: u64, authority: Pubkey) -> Self {
+        let t_total = total_supply as u128;
+        let r_total = u128::MAX - (u128::MAX % t_total);
+        ReflectionToken { t_total, r_total, t_fee_total: 0, authority }
+    }
+
+    /// Current r-space → t-space conversion rate.
+    pub fn rate(&self) -> u128 {
+        self.r_total / self.t_total
+    }
+
+    /// Derive a holder's displayed (t-space) balance from their `r_owned`.
+    pub fn balance_of(&self, holder: &Holder) -> u128 {
+        holder.r_owned / self.rate()
+    }
+
+    /// Move `amount` t-space tokens from `sender` to `recipient`, reflecting
+    /// a 10% fee to every holder by shrinking `r_total`.
+    pub fn transfer(
+        &mut self,
+        sender: &mut Holder,
+        recipient: &mut Holder,
+        amount: u128,
+    ) -> ProgramResult {
+        if self.t_total == 0 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if amount > self.balance_of(sender) {
+            return Err(ProgramError::InsufficientFunds);
+        }
+
+        let rate = self.rate();
+        let r_amount = amount * rate;
+        let t_fee = amount / 10;
+        let r_fee = t_fee * rate;
+        let r_transfer = r_amount - r_fee;
+
+        sender.r_owned -= r_amount;
+        recipient.r_owned += r_transfer;
+        self.r_total -= r_fee;
+        self.t_fee_total += t_fee;
+
+        Ok(())
+    }
+
+    /// Credit `amount` newly minted t-space tokens to `target`.
+    ///
+    /// `r_total` is pinned just below `u128::MAX`, so it cannot be grown to
+    /// cover the new supply without overflowing. Instead the reflected space
+    /// is re-pinned to the max-headroom value for the larger supply and the
+    /// target's balance is re-expressed at the new rate — the same rebasing
+    /// `new` performs, applied incrementally.
+    pub fn mint(&mut self, target: &mut Holder, amount: u128) -> ProgramResult {
+        if self.t_total == 0 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        // Capture the target's current balance, then add the minted amount.
+        let new_balance = self
+            .balance_of(target)
+            .checked_add(amount)
+            .ok_or(ProgramError::InvalidArgument)?;
+
+        // Grow the supply and re-pin the reflected space around it.
+        self.t_total = self
+            .t_total
+            .checked_add(amount)
+            .ok_or(ProgramError::InvalidArgument)?;
+        self.r_total = u128::MAX - (u128::MAX % self.t_total);
+
+        // Re-express the target's balance in the rescaled r-space.
+        target.r_owned = new_balance
+            .checked_mul(self.rate())
+            .ok_or(ProgramError::InvalidArgument)?;
+
+        Ok(())
+    }
+
+    /// Destroy `amount` t-space tokens held by `target`, shrinking the supply.
+    pub fn burn(&mut self, target: &mut Holder, amount: u128) -> ProgramResult {
+        if self.t_total == 0 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if amount > self.balance_of(target) {
+            return Err(ProgramError::InsufficientFunds);
+        }
+        // Never let the supply reach zero — a zero `t_total` poisons the
+        // account, making every later `rate()` divide by zero.
+        if amount >= self.t_total {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let r_amount = amount * self.rate();
+        self.t_total -= amount;
+        self.r_total -= r_amount;
+        target.r_owned -= r_amount;
+
+        Ok(())
+    }
+}
+
+impl solana_program::program_pack::Sealed for ReflectionToken {}
+
+impl Pack for ReflectionToken {
+    /// `t_total` + `r_total` + `t_fee_total` (16 each) plus the 32-byte authority.
+    const LEN: usize = 16 * 3 + 32;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let data = self.try_to_vec().unwrap();
+        dst[..data.len()].copy_from_slice(&data);
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        Self::try_from_slice(&src[..Self::LEN]).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+/// Require that `account` holds at least the rent-exempt minimum for its
+/// current data size, so a mutated account can't be silently reaped.
+fn assert_rent_exempt(account: &AccountInfo, rent: &Rent) -> ProgramResult {
+    let required = rent.minimum_balance(account.data_len());
+    if account.lamports() < required {
+        return Err(ProgramError::Custom(ERR_NOT_RENT_EXEMPT));
+    }
+    Ok(())
+}
+
+/// Require that `authority_info` is the token's stored authority and signed
+/// the transaction, as the gate for every privileged instruction.
+fn assert_authority(authority_info: &AccountInfo, token_data: &ReflectionToken) -> ProgramResult {
+    if authority_info.is_signer && authority_info.key == &token_data.authority {
+        Ok(())
+    } else {
+        Err(ProgramError::MissingRequiredSignature)
+    }
+}
+
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let token_account = next_account_info(accounts_iter)?;
+
+    let mut token_data = ReflectionToken::try_from_slice(&token_account.data.borrow())?;
+
+    let rent = Rent::get()?;
+
+    let instruction = ReflectionInstruction::try_from_slice(instruction_data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    match instruction {
+        // Initialize the token with total supply
+        ReflectionInstruction::Initialize { total_supply } => {
+            if total_supply == 0 {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+
+            let required_lamports = rent.minimum_balance(ReflectionToken::get_packed_len());
+
+            if token_account.lamports() < required_lamports {
+                return Err(ProgramError::InsufficientFunds);
+            }
+
+            let authority_info = next_account_info(accounts_iter)?;
+
+            token_data = ReflectionToken::new(total_supply, *authority_info.key);
+            token_data.serialize(&mut *token_account.data.borrow_mut())?;
+        }
+        // Transfer tokens
+        ReflectionInstruction::Transfer { amount } => {
+            let sender_info = next_account_info(accounts_iter)?;
+            let recipient_info = next_account_info(accounts_iter)?;
+
+            let amount = amount as u128;
+
+            if !(sender_info.is_signer && sender_info.is_writable && recipient_info.is_writable) {
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            assert_rent_exempt(token_account, &rent)?;
+            assert_rent_exempt(sender_info, &rent)?;
+            assert_rent_exempt(recipient_info, &rent)?;
+
+            let mut sender = Holder::try_from_slice(&sender_info.data.borrow())?;
+            let mut recipient = Holder::try_from_slice(&recipient_info.data.borrow())?;
+
+            token_data.transfer(&mut sender, &mut recipient, amount)?;
+
+            sender.serialize(&mut *sender_info.data.borrow_mut())?;
+            recipient.serialize(&mut *recipient_info.data.borrow_mut())?;
+            token_data.serialize(&mut *token_account.data.borrow_mut())?;
+        }
+        // Mint new tokens to a target account (authority only)
+        ReflectionInstruction::Mint { amount } => {
+            let authority_info = next_account_info(accounts_iter)?;
+            let target_info = next_account_info(accounts_iter)?;
+            assert_authority(authority_info, &token_data)?;
+
+            assert_rent_exempt(token_account, &rent)?;
+            assert_rent_exempt(target_info, &rent)?;
+
+            let mut target = Holder::try_from_slice(&target_info.data.borrow())?;
+            token_data.mint(&mut target, amount as u128)?;
+
+            target.serialize(&mut *target_info.data.borrow_mut())?;
+            token_data.serialize(&mut *token_account.data.borrow_mut())?;
+        }
+        // Burn tokens from a target account (authority only)
+        ReflectionInstruction::Burn { amount } => {
+            let authority_info = next_account_info(accounts_iter)?;
+            let target_info = next_account_info(accounts_iter)?;
+            assert_authority(authority_info, &token_data)?;
+
+            assert_rent_exempt(token_account, &rent)?;
+            assert_rent_exempt(target_info, &rent)?;
+
+            let mut target = Holder::try_from_slice(&target_info.data.borrow())?;
+            token_data.burn(&mut target, amount as u128)?;
+
+            target.serialize(&mut *target_info.data.borrow_mut())?;
+            token_data.serialize(&mut *token_account.data.borrow_mut())?;
+        }
+        // Transfer the authority to a new account (authority only)
+        ReflectionInstruction::SetAuthority { new } => {
+            let authority_info = next_account_info(accounts_iter)?;
+            assert_authority(authority_info, &token_data)?;
+
+            token_data.authority = new;
+            token_data.serialize(&mut *token_account.data.borrow_mut())?;
+        }
+        // Lend tokens to a borrower, requiring repayment plus a fee
+        ReflectionInstruction::FlashLoan { amount } => {
+            let pool_info = next_account_info(accounts_iter)?;
+            let borrower_info = next_account_info(accounts_iter)?;
+            let receiver_info = next_account_info(accounts_iter)?;
+
+            if token_data.t_total == 0 {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            let amount = amount as u128;
+            let fee = amount * FLASH_LOAN_FEE_BPS / 10_000;
+            let rate = token_data.rate();
+            let r_amount = amount * rate;
+            // Fee expressed in r-space at the lend-time rate; unlike a t-space
+            // balance it is invariant to rate changes during the callback.
+            let r_fee = fee * rate;
+
+            // Snapshot the pool in r-space, then lend to the borrower (the loan
+            // leg itself is not charged the reflection fee).
+            let mut pool = Holder::try_from_slice(&pool_info.data.borrow())?;
+            if amount > token_data.balance_of(&pool) {
+                return Err(ProgramError::InsufficientFunds);
+            }
+            let pre_r_owned = pool.r_owned;
+
+            assert_rent_exempt(pool_info, &rent)?;
+            assert_rent_exempt(borrower_info, &rent)?;
+
+            let mut borrower = Holder::try_from_slice(&borrower_info.data.borrow())?;
+            pool.r_owned -= r_amount;
+            borrower.r_owned += r_amount;
+            pool.serialize(&mut *pool_info.data.borrow_mut())?;
+            borrower.serialize(&mut *borrower_info.data.borrow_mut())?;
+
+            // Hand control to the borrower's callback, forwarding the pool,
+            // the borrower, and any pass-through accounts.
+            let mut metas = vec![
+                AccountMeta::new(*pool_info.key, false),
+                AccountMeta::new(*borrower_info.key, false),
+            ];
+            let mut infos = vec![pool_info.clone(), borrower_info.clone()];
+            for acc in accounts_iter {
+                metas.push(AccountMeta::new(*acc.key, acc.is_signer));
+                infos.push(acc.clone());
+            }
+            let callback = Instruction {
+                program_id: *receiver_info.key,
+                accounts: metas,
+                data: amount.to_le_bytes().to_vec(),
+            };
+            invoke_signed(&callback, &infos, &[])?;
+
+            // Require repayment in r-space. A reflecting transfer in the
+            // callback shrinks `r_total` and so inflates the pool's *derived*
+            // t-space balance without restoring `r_owned`; comparing raw
+            // `r_owned` is immune to that and proves the principal truly
+            // returned plus the fee.
+            let pool = Holder::try_from_slice(&pool_info.data.borrow())?;
+            if pool.r_owned < pre_r_owned + r_fee {
+                return Err(ProgramError::Custom(ERR_REPAYMENT_SHORTFALL));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+entrypoint!(process_instruction);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Seed a holder with the given t-space balance at the token's current rate.
+    fn holder_with(token: &ReflectionToken, balance: u128) -> Holder {
+        Holder { r_owned: balance * token.rate() }
+    }
+
+    #[test]
+    fn reflection_lifts_every_holder() {
+        // 900 supply split evenly across three holders.
+        let mut token = ReflectionToken::new(900, Pubkey::new_unique());
+        let mut a = holder_with(&token, 300);
+        let mut b = holder_with(&token, 300);
+        let bystander = holder_with(&token, 300);
+
+        assert_eq!(token.balance_of(&bystander), 300);
+
+        // A sends 90 to B; the 9-token fee reflects to all holders.
+        token.transfer(&mut a, &mut b, 90).unwrap();
+
+        assert_eq!(token.t_fee_total, 9);
+        // The bystander never moved but its derived balance grew.
+        assert!(token.balance_of(&bystander) > 300);
+    }
+
+    #[test]
+    fn transfer_rejects_overspend() {
+        let mut token = ReflectionToken::new(1000, Pubkey::new_unique());
+        let mut sender = holder_with(&token, 100);
+        let mut recipient = Holder::default();
+
+        assert_eq!(
+            token.transfer(&mut sender, &mut recipient, 101),
+            Err(ProgramError::InsufficientFunds)
+        );
+    }
+
+    #[test]
+    fn zero_amount_transfer_is_a_noop() {
+        let mut token = ReflectionToken::new(1000, Pubkey::new_unique());
+        let mut sender = holder_with(&token, 500);
+        let mut recipient = Holder::default();
+        let before = sender.r_owned;
+
+        token.transfer(&mut sender, &mut recipient, 0).unwrap();
+
+        assert_eq!(sender.r_owned, before);
+        assert_eq!(recipient.r_owned, 0);
+        assert_eq!(token.t_fee_total, 0);
+    }
+
+    #[test]
+    fn zero_supply_token_never_divides_by_zero() {
+        // A zero-supply token must error rather than panic in `rate()`.
+        let mut token = ReflectionToken {
+            t_total: 0,
+            r_total: 0,
+            t_fee_total: 0,
+            authority: Pubkey::new_unique(),
+        };
+        let mut a = Holder::default();
+        let mut b = Holder::default();
+
+        assert!(token.transfer(&mut a, &mut b, 1).is_err());
+        assert!(token.mint(&mut a, 1).is_err());
+        assert!(token.burn(&mut a, 1).is_err());
+    }
+
+    #[test]
+    fn burn_cannot_zero_the_supply() {
+        let mut token = ReflectionToken::new(1000, Pubkey::new_unique());
+        let mut whale = holder_with(&token, 1000);
+
+        assert_eq!(
+            token.burn(&mut whale, 1000),
+            Err(ProgramError::InvalidArgument)
+        );
+        // A partial burn that leaves supply behind still works.
+        token.burn(&mut whale, 400).unwrap();
+        assert_eq!(token.t_total, 600);
+    }
+
+    #[test]
+    fn mint_credits_target_without_overflowing() {
+        // Supplies that pin `r_total` right below u128::MAX — the overflow case
+        // the naive `r_total += r_amount` hit on every call.
+        for supply in [1000u64, 900, 1_000_000_000] {
+            let mut token = ReflectionToken::new(supply, Pubkey::new_unique());
+            let mut target = holder_with(&token, 100);
+
+            token.mint(&mut target, 50).unwrap();
+
+            assert_eq!(token.t_total, supply as u128 + 50);
+            assert_eq!(token.balance_of(&target), 150);
+        }
+    }
+
+    #[test]
+    fn authority_gate_requires_matching_signer() {
+        let authority = Pubkey::new_unique();
+        let token = ReflectionToken::new(1000, authority);
+        let owner = Pubkey::new_unique();
+        let mut lamports = 0u64;
+        let mut data: Vec<u8> = vec![];
+
+        // Right key, signed: accepted.
+        let signer = AccountInfo::new(
+            &authority, true, false, &mut lamports, &mut data, &owner, false, 0,
+        );
+        assert!(assert_authority(&signer, &token).is_ok());
+
+        // Right key, not a signer: rejected.
+        let mut l2 = 0u64;
+        let mut d2: Vec<u8> = vec![];
+        let non_signer = AccountInfo::new(
+            &authority, false, false, &mut l2, &mut d2, &owner, false, 0,
+        );
+        assert_eq!(
+            assert_authority(&non_signer, &token),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+
+        // Wrong key, signed: rejected.
+        let impostor = Pubkey::new_unique();
+        let mut l3 = 0u64;
+        let mut d3: Vec<u8> = vec![];
+        let wrong = AccountInfo::new(
+            &impostor, true, false, &mut l3, &mut d3, &owner, false, 0,
+        );
+        assert_eq!(
+            assert_authority(&wrong, &token),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+    }
+}